@@ -9,8 +9,9 @@ use crate::loom::cell::UnsafeCell;
 use crate::loom::sync::atomic::{AtomicU16, AtomicUsize};
 use crate::loom::sync::Mutex;
 use crate::util::linked_list::{self, LinkedList};
-// use crate::util::WakeList;
+use crate::util::WakeList;
 
+use std::fmt;
 use std::future::Future;
 use std::marker::PhantomPinned;
 use std::panic::{RefUnwindSafe, UnwindSafe};
@@ -197,10 +198,48 @@ type WaitList = LinkedList<Waiter, <Waiter as linked_list::Link>::Target>;
 /// [`notify_one()`]: Notify::notify_one()
 /// [`enable`]: Notified::enable()
 /// [`Semaphore`]: crate::sync::Semaphore
-#[derive(Debug)]
 pub struct Notify {
     state: AtomicU16,
     waiters: Mutex<WaitList>,
+
+    /// Number of `Waiter` nodes currently linked into `waiters`. Kept
+    /// alongside the mutex-guarded list so callers can inspect contention
+    /// without taking the lock.
+    waiters_len: AtomicUsize,
+
+    /// Permits banked beyond the single one `state == NOTIFIED` already
+    /// represents. Always `0` unless this `Notify` was built with
+    /// [`with_max_permits`](Notify::with_max_permits) and more than one
+    /// permit has been notified while no task was waiting.
+    permits: AtomicUsize,
+
+    /// Cap on the number of permits `notify_one`/`notify_last` will bank
+    /// while no task is waiting. `1` (the default from `new`/`const_new`)
+    /// reproduces the original saturating-at-one-permit behavior exactly,
+    /// since `permits` then never leaves `0`.
+    max_permits: usize,
+}
+
+impl fmt::Debug for Notify {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Relaxed/Acquire loads only: `Debug` must never perturb
+        // synchronization or take the `waiters` lock. Because nothing here
+        // is read under the lock, `state`, `waiters_len`, and `permits` can
+        // each change between one field's load and the next, so the
+        // printed struct is a racy snapshot, not an atomic one.
+        let state = match self.state.load(Acquire) {
+            EMPTY => "empty",
+            WAITING => "waiting",
+            NOTIFIED => "notified",
+            _ => "unknown",
+        };
+
+        f.debug_struct("Notify")
+            .field("state", &state)
+            .field("waiters_len", &self.waiters_len.load(Relaxed))
+            .field("permits", &self.permits.load(Relaxed))
+            .finish()
+    }
 }
 
 #[derive(Debug)]
@@ -353,6 +392,9 @@ impl Notify {
         Notify {
             state: AtomicU16::new(EMPTY),
             waiters: Mutex::new(LinkedList::new()),
+            waiters_len: AtomicUsize::new(0),
+            permits: AtomicUsize::new(0),
+            max_permits: 1,
         }
     }
 
@@ -378,9 +420,151 @@ impl Notify {
         Notify {
             state: AtomicU16::new(EMPTY),
             waiters: Mutex::const_new(LinkedList::new()),
+            waiters_len: AtomicUsize::new(0),
+            permits: AtomicUsize::new(0),
+            max_permits: 1,
+        }
+    }
+
+    /// Creates a new `Notify` that can bank up to `max_permits` permits.
+    ///
+    /// With the default `max_permits` of `1` (as constructed by [`new()`] or
+    /// [`const_new()`]), many sequential calls to `notify_one`/`notify_last`
+    /// while no task is waiting saturate into a single stored permit, and
+    /// the next `notified().await` completes immediately while the one
+    /// after that waits. With a larger `max_permits`, up to that many calls
+    /// are instead banked individually: `k` calls (for `k <=
+    /// max_permits`) leave `k` permits, each consumed by a separate
+    /// `notified().await`, turning `Notify` into a small counting event.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_permits` is `0`.
+    ///
+    /// [`new()`]: Notify::new
+    /// [`const_new()`]: Notify::const_new
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio::sync::Notify;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let notify = Notify::with_max_permits(2);
+    ///
+    ///     notify.notify_one();
+    ///     notify.notify_one();
+    ///
+    ///     // Both stored permits are consumed immediately.
+    ///     notify.notified().await;
+    ///     notify.notified().await;
+    /// }
+    /// ```
+    pub fn with_max_permits(max_permits: usize) -> Notify {
+        assert!(max_permits > 0, "max_permits must be greater than zero");
+        Notify {
+            max_permits,
+            ..Notify::new()
         }
     }
 
+    /// Returns the number of tasks currently parked in `notified().await`.
+    ///
+    /// This counts waiters that have registered themselves (via polling or
+    /// [`Notified::enable()`]) and have not yet been woken or dropped. It is
+    /// intended for backpressure heuristics and metrics; it is not useful for
+    /// synchronization, since the count may change concurrently with the
+    /// load.
+    ///
+    /// [`Notified::enable()`]: Notified::enable
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio::sync::Notify;
+    ///
+    /// let notify = Notify::new();
+    /// assert_eq!(notify.waiters_len(), 0);
+    /// ```
+    pub fn waiters_len(&self) -> usize {
+        self.waiters_len.load(Relaxed)
+    }
+
+    /// Returns `true` if a permit is currently stored, such that the next
+    /// call to [`notified().await`] would complete immediately without
+    /// registering a waiter.
+    ///
+    /// This accounts for permits banked beyond the single one `state ==
+    /// NOTIFIED` represents (see [`with_max_permits()`]), not just that
+    /// single permit.
+    ///
+    /// This does not consume the permit; it is a peek. A concurrent call to
+    /// [`notified().await`] may consume the permit between this check and a
+    /// subsequent await, so the result should be treated as a racy snapshot.
+    ///
+    /// [`notified().await`]: Notify::notified()
+    /// [`with_max_permits()`]: Notify::with_max_permits
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio::sync::Notify;
+    ///
+    /// let notify = Notify::new();
+    /// assert!(!notify.is_notified());
+    ///
+    /// notify.notify_one();
+    /// assert!(notify.is_notified());
+    /// ```
+    pub fn is_notified(&self) -> bool {
+        // `SeqCst` to match the loads/stores of `state` on the notify and
+        // poll paths; there is no fast path here worth relaxing the
+        // ordering for.
+        //
+        // `state == NOTIFIED` alone is not sufficient: a banked extra
+        // permit (only possible via `with_max_permits`) can be consumed by
+        // `notified().await` independently of `state`, so `state` can
+        // briefly read `EMPTY` while a permit is still banked -- check
+        // `permits` too, or this peek would go stale exactly when
+        // `with_max_permits` makes it interesting.
+        self.state.load(SeqCst) == NOTIFIED || self.permits.load(Relaxed) > 0
+    }
+
+    /// Returns `true` if a permit is currently stored.
+    ///
+    /// This is an alias for [`is_notified()`], kept so that it reads as the
+    /// counterpart to [`has_waiters()`] when checking the state of a
+    /// `Notify` for graceful-shutdown or backpressure purposes.
+    ///
+    /// [`is_notified()`]: Notify::is_notified
+    /// [`has_waiters()`]: Notify::has_waiters
+    pub fn has_permit(&self) -> bool {
+        self.is_notified()
+    }
+
+    /// Returns `true` if one or more tasks are currently parked in
+    /// [`notified().await`].
+    ///
+    /// This does not guarantee that a subsequent `notify_one()` will wake a
+    /// task: a waiter may be dropped (e.g. via cancellation) between this
+    /// check and the call to `notify_one()`, so the result should be
+    /// treated as a racy snapshot, not used for synchronization.
+    ///
+    /// [`notified().await`]: Notify::notified()
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio::sync::Notify;
+    ///
+    /// let notify = Notify::new();
+    /// assert!(!notify.has_waiters());
+    /// ```
+    pub fn has_waiters(&self) -> bool {
+        self.state.load(Acquire) == WAITING
+    }
+
     /// Wait for a notification.
     ///
     /// Equivalent to:
@@ -446,12 +630,17 @@ impl Notify {
     /// [`notified().await`] will complete immediately consuming the permit made
     /// available by this call to `notify_one()`.
     ///
+    /// Returns `true` if a currently waiting task was woken, or `false` if
+    /// no task was waiting and a permit was stored instead.
+    ///
     /// At most one permit may be stored by `Notify`. Many sequential calls to
     /// `notify_one` will result in a single permit being stored. The next call to
     /// `notified().await` will complete immediately, but the one after that
-    /// will wait.
+    /// will wait. A `Notify` built with [`with_max_permits()`] banks more
+    /// than one permit instead.
     ///
     /// [`notified().await`]: Notify::notified()
+    /// [`with_max_permits()`]: Notify::with_max_permits
     ///
     /// # Examples
     ///
@@ -475,8 +664,8 @@ impl Notify {
     /// ```
     // Alias for old name in 0.x
     #[cfg_attr(docsrs, doc(alias = "notify"))]
-    pub fn notify_one(&self) {
-        self.notify_with_strategy(NotifyOneStrategy::Fifo);
+    pub fn notify_one(&self) -> bool {
+        self.notify_with_strategy(NotifyOneStrategy::Fifo)
     }
 
     /// Notifies the last waiting task.
@@ -487,12 +676,107 @@ impl Notify {
     /// Check the [`notify_one()`] documentation for more info and
     /// examples.
     ///
+    /// Returns `true` if a currently waiting task was woken, or `false` if
+    /// no task was waiting and a permit was stored instead.
+    ///
     /// [`notify_one()`]: Notify::notify_one
-    pub fn notify_last(&self) {
-        self.notify_with_strategy(NotifyOneStrategy::Lifo);
+    pub fn notify_last(&self) -> bool {
+        self.notify_with_strategy(NotifyOneStrategy::Lifo)
     }
 
-    fn notify_with_strategy(&self, strategy: NotifyOneStrategy) {
+    /// Notifies a bounded batch of waiting tasks.
+    ///
+    /// Wakes up to `n` of the currently waiting tasks, in FIFO order, and
+    /// returns the number of tasks actually woken. Unlike `notify_one`, if
+    /// fewer than `n` tasks are waiting, the remaining notifications are
+    /// **not** stored as permits for later calls to [`notified().await`].
+    ///
+    /// This is useful for broadcast-style fan-out where a producer knows
+    /// exactly how many consumers should proceed (for example, releasing a
+    /// fixed number of semaphore-like permits), without the "one permit
+    /// max" limitation of repeated `notify_one` calls or the all-or-nothing
+    /// semantics of `notify_waiters`.
+    ///
+    /// [`notified().await`]: Notify::notified()
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tokio::sync::Notify;
+    ///
+    /// let notify = Notify::new();
+    /// assert_eq!(notify.notify_many(3), 0);
+    /// ```
+    pub fn notify_many(&self, n: usize) -> usize {
+        let mut remaining = n;
+        let mut woken = 0;
+
+        while remaining > 0 {
+            let mut waiters = self.waiters.lock();
+
+            if self.state.load(SeqCst) != WAITING {
+                break;
+            }
+
+            // Drain up to `WakeList`'s fixed capacity per lock acquisition,
+            // so we never hold `waiters` across an arbitrary number of
+            // (potentially user-supplied) `Waker::wake()` calls.
+            let mut wake_list = WakeList::new();
+            while remaining > 0 && wake_list.can_push() {
+                let waiter = match waiters.pop_back() {
+                    Some(waiter) => waiter,
+                    None => break,
+                };
+
+                // Safety: we never make mutable references to waiters.
+                let waiter = unsafe { waiter.as_ref() };
+
+                // Safety: we hold the lock, so we can access the waker.
+                let waker = unsafe { waiter.waker.with_mut(|waker| (*waker).take()) };
+
+                // This waiter is unlinked and will not be shared ever again, release it.
+                waiter.notification.store_release(NotifyOneStrategy::Fifo);
+                self.waiters_len.fetch_sub(1, Relaxed);
+
+                if let Some(waker) = waker {
+                    wake_list.push(waker);
+                }
+
+                remaining -= 1;
+                woken += 1;
+            }
+
+            let list_empty = waiters.is_empty();
+            if list_empty {
+                // As this was the **final** waiter in the list, the state
+                // must transition to `EMPTY`, just like `notify_locked`.
+                self.state.store(EMPTY, SeqCst);
+            }
+
+            // Drop the lock before waking, so arbitrary wake code never
+            // runs while `waiters` is held.
+            drop(waiters);
+            wake_list.wake_all();
+
+            if list_empty {
+                break;
+            }
+        }
+
+        woken
+    }
+
+    /// Tries to consume one permit banked in `self.permits`, i.e. a permit
+    /// beyond the single one `state == NOTIFIED` already represents.
+    /// Returns `true` if a permit was consumed, in which case `state`
+    /// should be left at `NOTIFIED` for the next waiter.
+    fn try_consume_extra_permit(&self) -> bool {
+        self.permits
+            .fetch_update(SeqCst, SeqCst, |p| (p > 0).then_some(p - 1))
+            .is_ok()
+    }
+
+    fn notify_with_strategy(&self, strategy: NotifyOneStrategy) -> bool {
         // Load the current state
         let mut curr = self.state.load(SeqCst);
 
@@ -504,8 +788,18 @@ impl Notify {
             let res = self.state.compare_exchange(curr, NOTIFIED, SeqCst, SeqCst);
 
             match res {
-                // No waiters, no further work to do
-                Ok(_) => return,
+                // No waiters, no further work to do. If a permit was
+                // already stored (`curr == NOTIFIED`) and this `Notify`
+                // allows more than one, bank an additional permit instead
+                // of coalescing into the existing one.
+                Ok(_) => {
+                    if curr == NOTIFIED && self.max_permits > 1 {
+                        let _ = self.permits.fetch_update(SeqCst, SeqCst, |p| {
+                            (p + 1 < self.max_permits).then_some(p + 1)
+                        });
+                    }
+                    return false;
+                }
                 Err(actual) => {
                     curr = actual;
                 }
@@ -519,10 +813,20 @@ impl Notify {
         // transition out of WAITING while the lock is held.
         curr = self.state.load(SeqCst);
 
-        if let Some(waker) = notify_locked(&mut waiters, &self.state, curr, strategy) {
-            drop(waiters);
+        let (woke_a_waiter, waker) = notify_locked(
+            &mut waiters,
+            &self.state,
+            curr,
+            strategy,
+            &self.waiters_len,
+            &self.permits,
+            self.max_permits,
+        );
+        drop(waiters);
+        if let Some(waker) = waker {
             waker.wake();
         }
+        woke_a_waiter
     }
 }
 
@@ -540,17 +844,26 @@ fn notify_locked(
     state: &AtomicU16,
     curr: u16,
     strategy: NotifyOneStrategy,
-) -> Option<Waker> {
+    waiters_len: &AtomicUsize,
+    permits: &AtomicUsize,
+    max_permits: usize,
+) -> (bool, Option<Waker>) {
     match curr {
         EMPTY | NOTIFIED => {
             let res = state.compare_exchange(curr, NOTIFIED, SeqCst, SeqCst);
 
             match res {
-                Ok(_) => None,
+                Ok(_) => {
+                    if curr == NOTIFIED && max_permits > 1 {
+                        let _ =
+                            permits.fetch_update(SeqCst, SeqCst, |p| (p + 1 < max_permits).then_some(p + 1));
+                    }
+                    (false, None)
+                }
                 Err(actual) => {
                     assert!(actual == EMPTY || actual == NOTIFIED);
                     state.store(NOTIFIED, SeqCst);
-                    None
+                    (false, None)
                 }
             }
         }
@@ -576,6 +889,9 @@ fn notify_locked(
                 .notification
                 .store_release(strategy);
 
+            // The waiter was just detached from the list.
+            waiters_len.fetch_sub(1, Relaxed);
+
             if waiters.is_empty() {
                 // As this the **final** waiter in the list, the state
                 // must be transitioned to `EMPTY`. As transitioning
@@ -583,7 +899,7 @@ fn notify_locked(
                 // `store` is sufficient.
                 state.store(EMPTY, SeqCst);
             }
-            waker
+            (true, waker)
         }
         _ => unreachable!(),
     }
@@ -718,6 +1034,17 @@ impl Notified<'_> {
         'outer_loop: loop {
             match *state {
                 State::Init => {
+                    // If this `Notify` has banked extra permits (only
+                    // possible when constructed via `with_max_permits`),
+                    // consume one of those first; `state` stays `NOTIFIED`
+                    // for the next waiter. `permits` can only ever be
+                    // nonzero when `max_permits > 1`, so skip the RMW
+                    // entirely for a default `Notify` -- the common case.
+                    if notify.max_permits > 1 && notify.try_consume_extra_permit() {
+                        *state = State::Done;
+                        continue 'outer_loop;
+                    }
+
                     // Optimistically try acquiring a pending notification
                     let res = notify.state.compare_exchange(
                         NOTIFIED,
@@ -771,6 +1098,14 @@ impl Notified<'_> {
                             }
                             WAITING => break,
                             NOTIFIED => {
+                                // Consume a banked extra permit first, if
+                                // any; see the matching check in the
+                                // `State::Init` branch above.
+                                if notify.max_permits > 1 && notify.try_consume_extra_permit() {
+                                    *state = State::Done;
+                                    continue 'outer_loop;
+                                }
+
                                 // Try consuming the notification
                                 let res = notify.state.compare_exchange(
                                     NOTIFIED,
@@ -809,6 +1144,7 @@ impl Notified<'_> {
 
                     // Insert the waiter into the linked list
                     waiters.push_front(NonNull::from(waiter));
+                    notify.waiters_len.fetch_add(1, Relaxed);
 
                     *state = State::Waiting;
 
@@ -926,6 +1262,14 @@ impl Drop for Notified<'_> {
             // list, then it is contained by a guarded list used by `notify_waiters`.
             unsafe { waiters.remove(NonNull::from(waiter)) };
 
+            // If the waiter had not yet been notified, it was still linked
+            // into `waiters` and the line above just detached it. If it had
+            // been notified, `notify_locked` or `notify_many` already
+            // decremented the count when it popped the node from the list.
+            if notification.is_none() {
+                notify.waiters_len.fetch_sub(1, Relaxed);
+            }
+
             if waiters.is_empty() && notify_state == WAITING {
                 notify.state.store(EMPTY, SeqCst);
             }
@@ -934,10 +1278,17 @@ impl Drop for Notified<'_> {
             // the notification was triggered via `notify_one`, it must be sent
             // to the next waiter.
             if let Some(strategy) = notification {
-                if let Some(waker) =
-                    notify_locked(&mut waiters, &notify.state, notify_state, strategy)
-                {
-                    drop(waiters);
+                let (_, waker) = notify_locked(
+                    &mut waiters,
+                    &notify.state,
+                    notify_state,
+                    strategy,
+                    &notify.waiters_len,
+                    &notify.permits,
+                    notify.max_permits,
+                );
+                drop(waiters);
+                if let Some(waker) = waker {
                     waker.wake();
                 }
             }
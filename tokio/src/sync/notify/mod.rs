@@ -103,7 +103,7 @@ generate_addr_of_methods! {
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(usize)]
 enum NotifyOneStrategy {
     Fifo,
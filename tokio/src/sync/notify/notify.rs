@@ -0,0 +1,323 @@
+use crate::loom::cell::UnsafeCell;
+use crate::loom::sync::atomic::AtomicUsize;
+use crate::loom::sync::Mutex;
+use crate::util::linked_list::{self, GuardedLinkedList, LinkedList};
+
+use std::future::Future;
+use std::pin::Pin;
+use std::ptr::NonNull;
+use std::sync::atomic::Ordering::{Acquire, Relaxed, SeqCst};
+use std::task::{Context, Poll, Waker};
+
+use super::{Notification, Waiter};
+
+type WaitList = LinkedList<Waiter, <Waiter as linked_list::Link>::Target>;
+type GuardedWaitList = GuardedLinkedList<Waiter, <Waiter as linked_list::Link>::Target>;
+
+/// Notifies all waiting tasks of an event.
+///
+/// Unlike [`Notify`], which delivers a notification to a single task,
+/// `NotifyMany` wakes every task currently parked in [`notified().await`]
+/// when [`notify_waiters()`] is called. A task that starts waiting only
+/// after a call to `notify_waiters()` is not woken by that call; it waits
+/// for the next one.
+///
+/// [`Notify`]: crate::sync::Notify
+/// [`notified().await`]: NotifyMany::notified()
+/// [`notify_waiters()`]: NotifyMany::notify_waiters()
+pub struct NotifyMany {
+    /// Number of calls to `notify_waiters` so far. Used by `Notified` to
+    /// detect a call that happened between the future's creation and its
+    /// first poll, which must still wake it.
+    state: AtomicUsize,
+    waiters: Mutex<WaitList>,
+
+    /// Number of `Waiter` nodes currently linked into `waiters`.
+    waiters_len: AtomicUsize,
+}
+
+impl std::fmt::Debug for NotifyMany {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Relaxed/Acquire loads only: `Debug` must never perturb
+        // synchronization or take the `waiters` lock.
+        f.debug_struct("NotifyMany")
+            .field("notify_waiters_calls", &self.state.load(Acquire))
+            .field("waiters_len", &self.waiters_len.load(Relaxed))
+            .finish()
+    }
+}
+
+impl NotifyMany {
+    /// Creates a new `NotifyMany`, initialized with no pending notification.
+    pub fn new() -> NotifyMany {
+        NotifyMany {
+            state: AtomicUsize::new(0),
+            waiters: Mutex::new(LinkedList::new()),
+            waiters_len: AtomicUsize::new(0),
+        }
+    }
+
+    /// Creates a new `NotifyMany`, initialized with no pending notification.
+    #[cfg(not(all(loom, test)))]
+    pub const fn const_new() -> NotifyMany {
+        NotifyMany {
+            state: AtomicUsize::new(0),
+            waiters: Mutex::const_new(LinkedList::new()),
+            waiters_len: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the number of tasks currently parked in `notified().await`.
+    ///
+    /// This is intended for backpressure heuristics and metrics; the count
+    /// may change concurrently with the load, so it should not be used for
+    /// synchronization.
+    pub fn waiters_len(&self) -> usize {
+        self.waiters_len.load(Relaxed)
+    }
+
+    /// Wait for a notification.
+    ///
+    /// Equivalent to:
+    ///
+    /// ```ignore
+    /// async fn notified(&self);
+    /// ```
+    ///
+    /// Each call to `notified()` returns a distinct future that completes
+    /// the next time [`notify_waiters()`] is called.
+    ///
+    /// [`notify_waiters()`]: NotifyMany::notify_waiters()
+    pub fn notified(&self) -> Notified<'_> {
+        Notified {
+            notify: self,
+            state: State::Init,
+            notify_waiters_calls: self.state.load(SeqCst),
+            waiter: Waiter::new(),
+        }
+    }
+
+    /// Notifies all waiting tasks.
+    ///
+    /// If no task is waiting, no permit is stored for a future call to
+    /// [`notified().await`] to consume, unlike [`Notify::notify_one()`].
+    /// Only tasks that were already waiting when this is called are woken.
+    ///
+    /// [`notified().await`]: NotifyMany::notified()
+    /// [`Notify::notify_one()`]: crate::sync::Notify::notify_one()
+    pub fn notify_waiters(&self) {
+        let mut waiters = self.waiters.lock();
+
+        // Bump the generation counter so any `Notified` future created
+        // before this point (even if not yet polled) observes the change.
+        self.state.fetch_add(1, SeqCst);
+
+        if waiters.is_empty() {
+            return;
+        }
+
+        // Using a guarded list lets a waiter concurrently dropped while we
+        // hold wakers outside the lock unlink itself safely; see
+        // `NotifyWaitersList` below. The guard node is never moved once
+        // pinned, so this is sound even though `Waiter` is `!Unpin`.
+        let guard = Waiter::new();
+        let guard = unsafe { Pin::new_unchecked(&guard) };
+        let mut list = NotifyWaitersList::new(std::mem::take(&mut *waiters), guard, self);
+
+        let mut wakers = Vec::new();
+        while let Some(waiter) = list.pop_back_locked(&mut waiters) {
+            // Safety: we never make mutable references to waiters.
+            let waiter = unsafe { waiter.as_ref() };
+
+            waiter.notification.store_release(Notification::All);
+            self.waiters_len.fetch_sub(1, Relaxed);
+
+            // Safety: we hold the lock and the waiter is already unlinked,
+            // so we have exclusive access to its waker.
+            if let Some(waker) = unsafe { waiter.waker.with_mut(|w| (*w).take()) } {
+                wakers.push(waker);
+            }
+        }
+
+        drop(waiters);
+        drop(list);
+
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+}
+
+impl Default for NotifyMany {
+    fn default() -> NotifyMany {
+        NotifyMany::new()
+    }
+}
+
+/// Future returned from [`NotifyMany::notified()`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Notified<'a> {
+    notify: &'a NotifyMany,
+    state: State,
+    notify_waiters_calls: usize,
+    waiter: Waiter,
+}
+
+unsafe impl Send for Notified<'_> {}
+unsafe impl Sync for Notified<'_> {}
+
+#[derive(Debug)]
+enum State {
+    Init,
+    Waiting,
+    Done,
+}
+
+impl Notified<'_> {
+    fn project(self: Pin<&mut Self>) -> (&NotifyMany, &mut State, usize, &Waiter) {
+        unsafe {
+            let me = self.get_unchecked_mut();
+            (me.notify, &mut me.state, me.notify_waiters_calls, &me.waiter)
+        }
+    }
+}
+
+impl Future for Notified<'_> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let (notify, state, notify_waiters_calls, waiter) = self.project();
+
+        loop {
+            match *state {
+                State::Init => {
+                    let mut waiters = notify.waiters.lock();
+
+                    // If a `notify_waiters()` call has happened since this
+                    // future was created, it is already "notified".
+                    if notify.state.load(SeqCst) != notify_waiters_calls {
+                        *state = State::Done;
+                        return Poll::Ready(());
+                    }
+
+                    // Safety: called while locked.
+                    unsafe {
+                        waiter
+                            .waker
+                            .with_mut(|v| *v = Some(cx.waker().clone()));
+                    }
+
+                    waiters.push_front(NonNull::from(waiter));
+                    notify.waiters_len.fetch_add(1, Relaxed);
+                    *state = State::Waiting;
+
+                    return Poll::Pending;
+                }
+                State::Waiting => {
+                    if waiter.notification.load(Acquire).is_some() {
+                        *state = State::Done;
+                        return Poll::Ready(());
+                    }
+
+                    let waiters = notify.waiters.lock();
+
+                    if waiter.notification.load(Relaxed).is_some() {
+                        drop(waiters);
+                        *state = State::Done;
+                        return Poll::Ready(());
+                    }
+
+                    // Safety: we hold the lock, so we may update the waker.
+                    unsafe {
+                        waiter.waker.with_mut(|v| {
+                            let should_update = match &*v {
+                                Some(current) => !current.will_wake(cx.waker()),
+                                None => true,
+                            };
+                            if should_update {
+                                *v = Some(cx.waker().clone());
+                            }
+                        });
+                    }
+
+                    return Poll::Pending;
+                }
+                State::Done => return Poll::Ready(()),
+            }
+        }
+    }
+}
+
+impl Drop for Notified<'_> {
+    fn drop(&mut self) {
+        let (notify, state, _, waiter) = unsafe { Pin::new_unchecked(self).project() };
+
+        if matches!(*state, State::Waiting) {
+            let mut waiters = notify.waiters.lock();
+
+            let already_notified = waiter.notification.load(Relaxed).is_some();
+
+            // Safety: we hold the lock, so we have exclusive access to every
+            // list the waiter may be contained in.
+            unsafe { waiters.remove(NonNull::from(waiter)) };
+
+            if !already_notified {
+                notify.waiters_len.fetch_sub(1, Relaxed);
+            }
+        }
+    }
+}
+
+/// List used in `NotifyMany::notify_waiters`. It wraps a guarded linked list
+/// and gates the access to it on `notify.waiters` mutex. It also empties
+/// the list on drop, so that a waiter concurrently dropped while we hold
+/// wakers outside the lock can still unlink itself.
+struct NotifyWaitersList<'a> {
+    list: GuardedWaitList,
+    is_empty: bool,
+    notify: &'a NotifyMany,
+}
+
+impl<'a> NotifyWaitersList<'a> {
+    fn new(
+        unguarded_list: WaitList,
+        guard: Pin<&'a Waiter>,
+        notify: &'a NotifyMany,
+    ) -> NotifyWaitersList<'a> {
+        let guard_ptr = NonNull::from(guard.get_ref());
+        let list = unguarded_list.into_guarded(guard_ptr);
+        NotifyWaitersList {
+            list,
+            is_empty: false,
+            notify,
+        }
+    }
+
+    /// Removes the last element from the guarded list. Modifying this list
+    /// requires an exclusive access to the main list in `NotifyMany`.
+    fn pop_back_locked(&mut self, _waiters: &mut WaitList) -> Option<NonNull<Waiter>> {
+        let result = self.list.pop_back();
+        if result.is_none() {
+            // Save information about emptiness to avoid waiting for the lock
+            // in the destructor.
+            self.is_empty = true;
+        }
+        result
+    }
+}
+
+impl Drop for NotifyWaitersList<'_> {
+    fn drop(&mut self) {
+        // If the list is not empty, we unlink all remaining waiters from it.
+        // We do not wake the waiters here to avoid double panics.
+        if !self.is_empty {
+            let _lock_guard = self.notify.waiters.lock();
+            while let Some(waiter) = self.list.pop_back() {
+                // Safety: we never make mutable references to waiters.
+                let waiter = unsafe { waiter.as_ref() };
+                waiter.notification.store_release(Notification::All);
+            }
+        }
+    }
+}
@@ -1,49 +1,197 @@
-use std::{pin::Pin, ptr::NonNull};
-use std::sync::atomic::Ordering::SeqCst;
-use crate::{loom::sync::{atomic::AtomicUsize, Mutex}, util::linked_list::{self, GuardedLinkedList, LinkedList}};
+use crate::loom::sync::atomic::{AtomicU16, AtomicUsize};
+use crate::loom::sync::Mutex;
+use crate::util::linked_list::{self, LinkedList};
 
-use super::{notify::Notify, Notification, NotifyOneStrategy, Waiter};
+use std::future::Future;
+use std::pin::Pin;
+use std::ptr::NonNull;
+use std::sync::atomic::Ordering::{Acquire, Relaxed, SeqCst};
+use std::task::{Context, Poll, Waker};
+
+use super::{Notification, NotifyOneStrategy, Waiter};
 
 type WaitList = LinkedList<Waiter, <Waiter as linked_list::Link>::Target>;
-type GuardedWaitList = GuardedLinkedList<Waiter, <Waiter as linked_list::Link>::Target>;
 
+/// Initial "idle" state.
+const EMPTY: u16 = 0;
+
+/// One or more threads are currently waiting to be notified.
+const WAITING: u16 = 1;
+
+/// Pending notification.
+const NOTIFIED: u16 = 2;
 
-// ! WIP - leaving for now
+/// Notifies a bounded number of waiting tasks at a time.
+///
+/// `NotifyAll` behaves like [`Notify`](crate::sync::Notify): [`notify_first()`]
+/// and [`notify_last()`] store at most one permit and wake a single waiter,
+/// exactly as [`Notify::notify_one()`]/[`Notify::notify_last()`] do. In
+/// addition, [`notify_n()`] and [`notify_n_last()`] wake up to a bounded
+/// number of waiters in one call. Unlike the single-waiter methods, if
+/// fewer waiters are parked than were asked for, the unused wakeups are
+/// **not** stored as permits; this matches the all-or-nothing semantics of
+/// [`NotifyMany::notify_waiters()`], just bounded to a count instead of
+/// unbounded.
+///
+/// [`notify_first()`]: NotifyAll::notify_first
+/// [`notify_last()`]: NotifyAll::notify_last
+/// [`notify_n()`]: NotifyAll::notify_n
+/// [`notify_n_last()`]: NotifyAll::notify_n_last
+/// [`Notify::notify_one()`]: crate::sync::Notify::notify_one()
+/// [`Notify::notify_last()`]: crate::sync::Notify::notify_last()
+/// [`NotifyMany::notify_waiters()`]: super::notify::NotifyMany::notify_waiters()
 pub struct NotifyAll {
-    state: AtomicUsize,
+    state: AtomicU16,
     waiters: Mutex<WaitList>,
+
+    /// Number of `Waiter` nodes currently linked into `waiters`.
+    waiters_len: AtomicUsize,
+}
+
+impl std::fmt::Debug for NotifyAll {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Relaxed/Acquire loads only: `Debug` must never perturb
+        // synchronization or take the `waiters` lock, so `state` and
+        // `waiters_len` may each change between loads -- this is a racy
+        // snapshot, not an atomic one.
+        let state = match self.state.load(Acquire) {
+            EMPTY => "empty",
+            WAITING => "waiting",
+            NOTIFIED => "notified",
+            _ => "unknown",
+        };
+
+        f.debug_struct("NotifyAll")
+            .field("state", &state)
+            .field("waiters_len", &self.waiters_len.load(Relaxed))
+            .finish()
+    }
 }
 
 impl NotifyAll {
+    /// Creates a new `NotifyAll`, initialized without a permit.
+    pub fn new() -> NotifyAll {
+        NotifyAll {
+            state: AtomicU16::new(EMPTY),
+            waiters: Mutex::new(LinkedList::new()),
+            waiters_len: AtomicUsize::new(0),
+        }
+    }
 
-    pub fn notify_first(&self) {
-        self.notify_with_strategy(NotifyOneStrategy::Fifo);
+    /// Creates a new `NotifyAll`, initialized without a permit.
+    #[cfg(not(all(loom, test)))]
+    pub const fn const_new() -> NotifyAll {
+        NotifyAll {
+            state: AtomicU16::new(EMPTY),
+            waiters: Mutex::const_new(LinkedList::new()),
+            waiters_len: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the number of tasks currently parked in `notified().await`.
+    pub fn waiters_len(&self) -> usize {
+        self.waiters_len.load(Relaxed)
+    }
+
+    /// Wait for a notification.
+    pub fn notified(&self) -> Notified<'_> {
+        Notified {
+            notify: self,
+            state: State::Init,
+            waiter: Waiter::new(),
+        }
     }
 
-    /// Notifies the last waiting task.
+    /// Notifies the oldest waiting task.
     ///
-    /// This function behaves similar to `notify_one`. The only difference is that it wakes
-    /// the most recently added waiter instead of the oldest waiter.
+    /// Check [`Notify::notify_one()`] for the single-waiter semantics this
+    /// mirrors.
     ///
-    /// Check the [`notify_one()`] documentation for more info and
-    /// examples.
+    /// [`Notify::notify_one()`]: crate::sync::Notify::notify_one()
+    pub fn notify_first(&self) {
+        self.notify_with_strategy(NotifyOneStrategy::Fifo);
+    }
+
+    /// Notifies the most recently added waiting task.
     ///
-    /// [`notify_one()`]: Notify::notify_one
+    /// This function behaves similar to `notify_first`. The only difference is
+    /// that it wakes the most recently added waiter instead of the oldest
+    /// waiter.
     pub fn notify_last(&self) {
         self.notify_with_strategy(NotifyOneStrategy::Lifo);
     }
 
+    /// Notifies up to `count` of the currently waiting tasks, in FIFO order,
+    /// and returns the number of tasks actually woken.
+    ///
+    /// Unlike `notify_first`, if fewer than `count` tasks are waiting, the
+    /// remaining notifications are **not** stored as permits for later calls
+    /// to [`notified().await`].
+    ///
+    /// [`notified().await`]: NotifyAll::notified()
+    pub fn notify_n(&self, count: usize) -> usize {
+        self.notify_n_with_strategy(count, NotifyOneStrategy::Fifo)
+    }
+
+    /// Notifies up to `count` of the currently waiting tasks, in LIFO order,
+    /// and returns the number of tasks actually woken.
+    ///
+    /// Check [`notify_n()`] for more info; the only difference is the order
+    /// in which waiters are woken.
+    ///
+    /// [`notify_n()`]: NotifyAll::notify_n
+    pub fn notify_n_last(&self, count: usize) -> usize {
+        self.notify_n_with_strategy(count, NotifyOneStrategy::Lifo)
+    }
+
+    fn notify_n_with_strategy(&self, count: usize, strategy: NotifyOneStrategy) -> usize {
+        if count == 0 {
+            return 0;
+        }
+
+        // Hold the lock across the whole batch, so the count of waiters we
+        // wake is exact, then wake everyone after releasing it so arbitrary
+        // wake code never runs while `waiters` is held.
+        let mut waiters = self.waiters.lock();
+        let mut wakers = Vec::new();
+        let mut woken = 0;
+
+        while woken < count {
+            let curr = self.state.load(SeqCst);
+            if curr != WAITING {
+                // Fewer than `count` tasks were waiting; the remaining
+                // notifications are intentionally dropped rather than
+                // stored as a permit.
+                break;
+            }
+
+            if let Some(waker) =
+                notify_locked(&mut waiters, &self.state, curr, strategy, &self.waiters_len)
+            {
+                wakers.push(waker);
+            }
+            woken += 1;
+        }
+
+        drop(waiters);
+
+        for waker in wakers {
+            waker.wake();
+        }
+
+        woken
+    }
+
     fn notify_with_strategy(&self, strategy: NotifyOneStrategy) {
         // Load the current state
         let mut curr = self.state.load(SeqCst);
 
         // If the state is `EMPTY`, transition to `NOTIFIED` and return.
-        while let EMPTY | NOTIFIED = get_state(curr) {
+        while let EMPTY | NOTIFIED = curr {
             // The compare-exchange from `NOTIFIED` -> `NOTIFIED` is intended. A
             // happens-before synchronization must happen between this atomic
             // operation and a task calling `notified().await`.
-            let new = set_state(curr, NOTIFIED);
-            let res = self.state.compare_exchange(curr, new, SeqCst, SeqCst);
+            let res = self.state.compare_exchange(curr, NOTIFIED, SeqCst, SeqCst);
 
             match res {
                 // No waiters, no further work to do
@@ -61,60 +209,248 @@ impl NotifyAll {
         // transition out of WAITING while the lock is held.
         curr = self.state.load(SeqCst);
 
-        if let Some(waker) = notify_locked(&mut waiters, &self.state, curr, strategy) {
+        if let Some(waker) =
+            notify_locked(&mut waiters, &self.state, curr, strategy, &self.waiters_len)
+        {
             drop(waiters);
             waker.wake();
         }
     }
 }
 
-/// List used in `Notify::notify_waiters`. It wraps a guarded linked list
-/// and gates the access to it on `notify.waiters` mutex. It also empties
-/// the list on drop.
-struct NotifyWaitersList<'a> {
-    list: GuardedWaitList,
-    is_empty: bool,
-    notify: &'a Notify,
+impl Default for NotifyAll {
+    fn default() -> NotifyAll {
+        NotifyAll::new()
+    }
 }
 
-impl<'a> NotifyWaitersList<'a> {
-    fn new(
-        unguarded_list: WaitList,
-        guard: Pin<&'a Waiter>,
-        notify: &'a Notify,
-    ) -> NotifyWaitersList<'a> {
-        let guard_ptr = NonNull::from(guard.get_ref());
-        let list = unguarded_list.into_guarded(guard_ptr);
-        NotifyWaitersList {
-            list,
-            is_empty: false,
-            notify,
+/// Pops and notifies a single waiter using the given strategy, or stores a
+/// permit in `state` if none is currently waiting.
+///
+/// This must only be called while `waiters`'s lock is held and `curr` is the
+/// most recently observed value of `state` under that lock.
+fn notify_locked(
+    waiters: &mut WaitList,
+    state: &AtomicU16,
+    curr: u16,
+    strategy: NotifyOneStrategy,
+    waiters_len: &AtomicUsize,
+) -> Option<Waker> {
+    match curr {
+        EMPTY | NOTIFIED => {
+            let res = state.compare_exchange(curr, NOTIFIED, SeqCst, SeqCst);
+
+            match res {
+                Ok(_) => None,
+                Err(actual) => {
+                    debug_assert!(actual == EMPTY || actual == NOTIFIED);
+                    state.store(NOTIFIED, SeqCst);
+                    None
+                }
+            }
+        }
+        WAITING => {
+            // At this point, it is guaranteed that the state will not
+            // concurrently change as holding the lock is required to
+            // transition **out** of `WAITING`.
+            let waiter = match strategy {
+                NotifyOneStrategy::Fifo => waiters.pop_back().unwrap(),
+                NotifyOneStrategy::Lifo => waiters.pop_front().unwrap(),
+            };
+
+            // Safety: we never make mutable references to waiters.
+            let waiter = unsafe { waiter.as_ref() };
+
+            // Safety: we hold the lock, so we can access the waker.
+            let waker = unsafe { waiter.waker.with_mut(|waker| (*waker).take()) };
+
+            // This waiter is unlinked and will not be shared ever again, release it.
+            waiter.notification.store_release(Notification::One(strategy));
+
+            // The waiter was just detached from the list.
+            waiters_len.fetch_sub(1, Relaxed);
+
+            if waiters.is_empty() {
+                // As this is the **final** waiter in the list, the state
+                // must be transitioned to `EMPTY`. As transitioning
+                // **from** `WAITING` requires the lock to be held, a
+                // `store` is sufficient.
+                state.store(EMPTY, SeqCst);
+            }
+
+            waker
         }
+        _ => unreachable!(),
     }
+}
+
+/// Future returned from [`NotifyAll::notified()`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Notified<'a> {
+    notify: &'a NotifyAll,
+    state: State,
+    waiter: Waiter,
+}
+
+unsafe impl Send for Notified<'_> {}
+unsafe impl Sync for Notified<'_> {}
+
+#[derive(Debug)]
+enum State {
+    Init,
+    Waiting,
+    Done,
+}
+
+impl Notified<'_> {
+    fn project(self: Pin<&mut Self>) -> (&NotifyAll, &mut State, &Waiter) {
+        unsafe {
+            // Safety: `notify` and `state` are `Unpin`; `waiter` is pinned
+            // alongside `self` and never moved out of.
+            let me = self.get_unchecked_mut();
+            (me.notify, &mut me.state, &me.waiter)
+        }
+    }
+}
+
+impl Future for Notified<'_> {
+    type Output = ();
 
-    /// Removes the last element from the guarded list. Modifying this list
-    /// requires an exclusive access to the main list in `Notify`.
-    fn pop_back_locked(&mut self, _waiters: &mut WaitList) -> Option<NonNull<Waiter>> {
-        let result = self.list.pop_back();
-        if result.is_none() {
-            // Save information about emptiness to avoid waiting for lock
-            // in the destructor.
-            self.is_empty = true;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let (notify, state, waiter) = self.project();
+
+        loop {
+            match *state {
+                State::Init => {
+                    // Optimistically try acquiring a pending notification.
+                    let res = notify.state.compare_exchange(NOTIFIED, EMPTY, SeqCst, SeqCst);
+
+                    if res.is_ok() {
+                        *state = State::Done;
+                        return Poll::Ready(());
+                    }
+
+                    let mut waiters = notify.waiters.lock();
+                    let mut curr = notify.state.load(SeqCst);
+
+                    loop {
+                        match curr {
+                            EMPTY => {
+                                let res =
+                                    notify.state.compare_exchange(EMPTY, WAITING, SeqCst, SeqCst);
+                                match res {
+                                    Ok(_) => break,
+                                    Err(actual) => {
+                                        debug_assert_eq!(actual, NOTIFIED);
+                                        curr = actual;
+                                    }
+                                }
+                            }
+                            WAITING => break,
+                            NOTIFIED => {
+                                let res =
+                                    notify.state.compare_exchange(NOTIFIED, EMPTY, SeqCst, SeqCst);
+                                match res {
+                                    Ok(_) => {
+                                        drop(waiters);
+                                        *state = State::Done;
+                                        return Poll::Ready(());
+                                    }
+                                    Err(actual) => {
+                                        debug_assert_eq!(actual, EMPTY);
+                                        curr = actual;
+                                    }
+                                }
+                            }
+                            _ => unreachable!(),
+                        }
+                    }
+
+                    // Safety: called while locked.
+                    unsafe {
+                        waiter.waker.with_mut(|v| *v = Some(cx.waker().clone()));
+                    }
+
+                    waiters.push_front(NonNull::from(waiter));
+                    notify.waiters_len.fetch_add(1, Relaxed);
+                    *state = State::Waiting;
+
+                    return Poll::Pending;
+                }
+                State::Waiting => {
+                    if waiter.notification.load(Acquire).is_some() {
+                        *state = State::Done;
+                        return Poll::Ready(());
+                    }
+
+                    let waiters = notify.waiters.lock();
+
+                    if waiter.notification.load(Relaxed).is_some() {
+                        drop(waiters);
+                        *state = State::Done;
+                        return Poll::Ready(());
+                    }
+
+                    // Safety: we hold the lock, so we may update the waker.
+                    unsafe {
+                        waiter.waker.with_mut(|v| {
+                            let should_update = match &*v {
+                                Some(current) => !current.will_wake(cx.waker()),
+                                None => true,
+                            };
+                            if should_update {
+                                *v = Some(cx.waker().clone());
+                            }
+                        });
+                    }
+
+                    return Poll::Pending;
+                }
+                State::Done => return Poll::Ready(()),
+            }
         }
-        result
     }
 }
 
-impl Drop for NotifyWaitersList<'_> {
+impl Drop for Notified<'_> {
     fn drop(&mut self) {
-        // If the list is not empty, we unlink all waiters from it.
-        // We do not wake the waiters to avoid double panics.
-        if !self.is_empty {
-            let _lock_guard = self.notify.waiter.lock();
-            while let Some(waiter) = self.list.pop_back() {
-                // Safety: we never make mutable references to waiters.
-                let waiter = unsafe { waiter.as_ref() };
-                waiter.notification.store_release(Notification::All);
+        // Safety: the type only transitions to `State::Waiting` when pinned.
+        let (notify, state, waiter) = unsafe { Pin::new_unchecked(self).project() };
+
+        if matches!(*state, State::Waiting) {
+            let mut waiters = notify.waiters.lock();
+            let notify_state = notify.state.load(SeqCst);
+
+            // We hold the lock, so this field is not concurrently accessed
+            // by `notify_*` functions and we can use relaxed ordering.
+            let notification = waiter.notification.load(Relaxed);
+
+            // Safety: we hold the lock, so we have exclusive access to every
+            // list the waiter may be contained in.
+            unsafe { waiters.remove(NonNull::from(waiter)) };
+
+            if notification.is_none() {
+                notify.waiters_len.fetch_sub(1, Relaxed);
+            }
+
+            if waiters.is_empty() && notify_state == WAITING {
+                notify.state.store(EMPTY, SeqCst);
+            }
+
+            // See if the node was notified but not received. In this case,
+            // the notification must be sent on to the next waiter.
+            if let Some(Notification::One(strategy)) = notification {
+                if let Some(waker) = notify_locked(
+                    &mut waiters,
+                    &notify.state,
+                    notify_state,
+                    strategy,
+                    &notify.waiters_len,
+                ) {
+                    drop(waiters);
+                    waker.wake();
+                }
             }
         }
     }
@@ -46,6 +46,219 @@ fn notify_clones_waker_before_lock() {
 }
 
 
+// The intrusive linked-list notifier code here is exactly where a subtle
+// leak or double-free would hide, so these tests drop `Notified` futures
+// at every interleaving point around `notify_one`/`notify_last` and assert
+// (via a waker that counts its own drops) that each waiter's waker is
+// unlinked from the wait list and released exactly once. They run as
+// ordinary tests, and are additionally run by CI under Miri with
+// `-Zmiri-ignore-leaks`.
+mod ignore_leaks {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// A waker that records exactly how many times it has been dropped, so
+    /// tests can assert the wait-list's detach-on-drop paths release each
+    /// waiter's waker exactly once.
+    fn counting_waker() -> (Waker, Arc<AtomicUsize>) {
+        struct CountDrops(Arc<AtomicUsize>);
+
+        impl Drop for CountDrops {
+            fn drop(&mut self) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let count = Arc::new(AtomicUsize::new(0));
+        let tracker = Arc::new(CountDrops(count.clone()));
+
+        const VTABLE: &RawWakerVTable = &RawWakerVTable::new(clone_w, wake, wake_by_ref, drop_w);
+
+        unsafe fn clone_w(data: *const ()) -> RawWaker {
+            Arc::<CountDrops>::increment_strong_count(data as *const CountDrops);
+            RawWaker::new(data, VTABLE)
+        }
+
+        // `wake` consumes the raw waker, so it must drop the implicit
+        // reference; `wake_by_ref` does not own a reference and must not.
+        unsafe fn wake(data: *const ()) {
+            drop_w(data);
+        }
+
+        unsafe fn wake_by_ref(_data: *const ()) {}
+
+        unsafe fn drop_w(data: *const ()) {
+            drop(Arc::<CountDrops>::from_raw(data as *const CountDrops));
+        }
+
+        let waker =
+            unsafe { Waker::from_raw(RawWaker::new(Arc::into_raw(tracker) as *const _, VTABLE)) };
+
+        (waker, count)
+    }
+
+    #[test]
+    fn waiter_waker_dropped_once_on_notify_one() {
+        let notify = Notify::new();
+        let (waker, drops) = counting_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        let future = notify.notified();
+        pin!(future);
+        assert!(future.as_mut().poll(&mut cx).is_pending());
+
+        notify.notify_one();
+        assert!(future.as_mut().poll(&mut cx).is_ready());
+
+        // The stored waker is taken (and dropped) exactly once, either by
+        // `notify_locked` when it pops the waiter, or by `poll_notified`
+        // when it observes the notification -- never both.
+        assert_eq!(drops.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn waiter_waker_dropped_once_when_dropped_while_enqueued() {
+        let notify = Notify::new();
+        let (waker, drops) = counting_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        let future = notify.notified();
+        pin!(future);
+        assert!(future.as_mut().poll(&mut cx).is_pending());
+        assert_eq!(notify.waiters_len(), 1);
+
+        drop(future);
+
+        assert_eq!(notify.waiters_len(), 0);
+        assert_eq!(drops.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn waiter_waker_dropped_once_on_notify_last() {
+        let notify = Notify::new();
+        let (waker1, drops1) = counting_waker();
+        let (waker2, drops2) = counting_waker();
+        let mut cx1 = Context::from_waker(&waker1);
+        let mut cx2 = Context::from_waker(&waker2);
+
+        let future1 = notify.notified();
+        pin!(future1);
+        assert!(future1.as_mut().poll(&mut cx1).is_pending());
+
+        let future2 = notify.notified();
+        pin!(future2);
+        assert!(future2.as_mut().poll(&mut cx2).is_pending());
+
+        notify.notify_last();
+
+        assert!(future2.as_mut().poll(&mut cx2).is_ready());
+        assert_eq!(drops2.load(Ordering::SeqCst), 1);
+
+        // `future1` was never notified; dropping it must still release its
+        // waker exactly once.
+        drop(future1);
+        assert_eq!(drops1.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn waiter_waker_dropped_once_when_dropped_after_notify_one() {
+        let notify = Notify::new();
+        let (waker, drops) = counting_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        let future = notify.notified();
+        pin!(future);
+        assert!(future.as_mut().poll(&mut cx).is_pending());
+
+        notify.notify_one();
+
+        // Dropping a future that was notified but never re-polled must
+        // still release its waker exactly once, and must forward the
+        // notification to the next waiter (none here) rather than drop it
+        // silently.
+        drop(future);
+
+        assert_eq!(drops.load(Ordering::SeqCst), 1);
+        assert_eq!(notify.waiters_len(), 0);
+    }
+}
+
+#[test]
+fn notify_many_wakes_multiple_waiters_across_wake_list_chunks() {
+    let notify = Notify::new();
+
+    // More waiters than a single `WakeList` batch holds, so `notify_many`
+    // must loop through its drain/relock cycle more than once.
+    const N: usize = 40;
+
+    let mut futs = Vec::new();
+    for _ in 0..N {
+        let mut fut = tokio_test::task::spawn(notify.notified());
+        assert!(fut.poll().is_pending());
+        futs.push(fut);
+    }
+    assert_eq!(notify.waiters_len(), N);
+
+    let woken = notify.notify_many(N);
+    assert_eq!(woken, N);
+    assert_eq!(notify.waiters_len(), 0);
+
+    for fut in &mut futs {
+        assert!(fut.poll().is_ready());
+    }
+}
+
+#[test]
+fn notify_many_does_not_store_unused_notifications_as_permits() {
+    let notify = Notify::new();
+
+    let mut futs = Vec::new();
+    for _ in 0..3 {
+        let mut fut = tokio_test::task::spawn(notify.notified());
+        assert!(fut.poll().is_pending());
+        futs.push(fut);
+    }
+
+    // Ask for more wakeups than there are waiters.
+    let woken = notify.notify_many(10);
+    assert_eq!(woken, 3);
+
+    for fut in &mut futs {
+        assert!(fut.poll().is_ready());
+    }
+
+    // The 7 unused notifications must not be banked as a permit: a task
+    // that starts waiting afterwards should not complete immediately.
+    let mut late = tokio_test::task::spawn(notify.notified());
+    assert!(late.poll().is_pending());
+}
+
+#[test]
+fn debug_reports_state_and_waiters_len() {
+    use futures::task::noop_waker;
+
+    let notify = Notify::new();
+    assert_eq!(
+        format!("{:?}", notify),
+        "Notify { state: \"empty\", waiters_len: 0, permits: 0 }"
+    );
+
+    notify.notify_one();
+    assert_eq!(
+        format!("{:?}", notify),
+        "Notify { state: \"notified\", waiters_len: 0, permits: 0 }"
+    );
+
+    let waker = noop_waker();
+    let future = notify.notified();
+    pin!(future);
+    let _ = future.as_mut().poll(&mut Context::from_waker(&waker));
+    assert_eq!(
+        format!("{:?}", notify),
+        "Notify { state: \"empty\", waiters_len: 0, permits: 0 }"
+    );
+}
+
 #[test]
 #[cfg(not(target_family = "wasm"))]
 fn watch_test() {
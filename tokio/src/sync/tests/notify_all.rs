@@ -0,0 +1,124 @@
+use crate::sync::NotifyAll;
+
+#[cfg(all(target_family = "wasm", not(target_os = "wasi")))]
+use wasm_bindgen_test::wasm_bindgen_test as test;
+
+#[test]
+fn notify_n_wakes_exactly_count() {
+    let notify = NotifyAll::new();
+
+    let mut futs = Vec::new();
+    for _ in 0..5 {
+        let mut fut = tokio_test::task::spawn(notify.notified());
+        assert!(fut.poll().is_pending());
+        futs.push(fut);
+    }
+    assert_eq!(notify.waiters_len(), 5);
+
+    let woken = notify.notify_n(3);
+    assert_eq!(woken, 3);
+    assert_eq!(notify.waiters_len(), 2);
+
+    let ready = futs.iter_mut().filter(|f| f.poll().is_ready()).count();
+    assert_eq!(ready, 3);
+}
+
+#[test]
+fn notify_n_more_than_waiters_does_not_store_permit() {
+    let notify = NotifyAll::new();
+
+    let mut futs = Vec::new();
+    for _ in 0..2 {
+        let mut fut = tokio_test::task::spawn(notify.notified());
+        assert!(fut.poll().is_pending());
+        futs.push(fut);
+    }
+
+    let woken = notify.notify_n(5);
+    assert_eq!(woken, 2);
+
+    for fut in &mut futs {
+        assert!(fut.poll().is_ready());
+    }
+
+    // The 3 unused notifications must not be banked as a permit: a task
+    // that starts waiting afterwards should not complete immediately.
+    let mut late = tokio_test::task::spawn(notify.notified());
+    assert!(late.poll().is_pending());
+}
+
+#[test]
+fn notify_n_zero_wakes_nobody() {
+    let notify = NotifyAll::new();
+
+    let mut futs = Vec::new();
+    for _ in 0..3 {
+        let mut fut = tokio_test::task::spawn(notify.notified());
+        assert!(fut.poll().is_pending());
+        futs.push(fut);
+    }
+
+    assert_eq!(notify.notify_n(0), 0);
+    assert_eq!(notify.waiters_len(), 3);
+
+    for fut in &mut futs {
+        assert!(fut.poll().is_pending());
+    }
+}
+
+#[test]
+fn notify_n_is_fifo() {
+    let notify = NotifyAll::new();
+
+    let mut first = tokio_test::task::spawn(notify.notified());
+    assert!(first.poll().is_pending());
+    let mut second = tokio_test::task::spawn(notify.notified());
+    assert!(second.poll().is_pending());
+
+    // `notify_n` wakes the oldest (first-parked) waiter first.
+    assert_eq!(notify.notify_n(1), 1);
+    assert!(first.poll().is_ready());
+    assert!(second.poll().is_pending());
+}
+
+#[test]
+fn notify_n_last_is_lifo() {
+    let notify = NotifyAll::new();
+
+    let mut first = tokio_test::task::spawn(notify.notified());
+    assert!(first.poll().is_pending());
+    let mut second = tokio_test::task::spawn(notify.notified());
+    assert!(second.poll().is_pending());
+
+    // `notify_n_last` wakes the most-recently-parked waiter first.
+    assert_eq!(notify.notify_n_last(1), 1);
+    assert!(second.poll().is_ready());
+    assert!(first.poll().is_pending());
+}
+
+#[test]
+fn notify_n_transitions_to_empty_after_final_waiter() {
+    let notify = NotifyAll::new();
+
+    let mut futs = Vec::new();
+    for _ in 0..2 {
+        let mut fut = tokio_test::task::spawn(notify.notified());
+        assert!(fut.poll().is_pending());
+        futs.push(fut);
+    }
+    assert_eq!(
+        format!("{:?}", notify),
+        "NotifyAll { state: \"waiting\", waiters_len: 2 }"
+    );
+
+    let woken = notify.notify_n(2);
+    assert_eq!(woken, 2);
+    assert_eq!(
+        format!("{:?}", notify),
+        "NotifyAll { state: \"empty\", waiters_len: 0 }"
+    );
+
+    for fut in &mut futs {
+        assert!(fut.poll().is_ready());
+    }
+}
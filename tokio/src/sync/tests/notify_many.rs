@@ -60,4 +60,46 @@ fn notify_simple() {
 
     assert!(fut1.poll().is_ready());
     assert!(fut2.poll().is_ready());
+}
+
+// See the matching module in `notify.rs` for why these run under Miri
+// with `-Zmiri-ignore-leaks` in CI, in addition to as ordinary tests.
+mod ignore_leaks {
+    use super::*;
+
+    #[test]
+    fn waiters_len_tracks_drop_before_notify_waiters() {
+        let notify = NotifyMany::new();
+
+        let mut fut = tokio_test::task::spawn(notify.notified());
+        assert!(fut.poll().is_pending());
+        assert_eq!(notify.waiters_len(), 1);
+
+        drop(fut);
+        assert_eq!(notify.waiters_len(), 0);
+
+        // Dropping before `notify_waiters` must fully unlink the waiter, so
+        // a subsequent call has nothing left to wake or leak.
+        notify.notify_waiters();
+    }
+
+    #[test]
+    fn waiters_len_empty_after_notify_waiters() {
+        let notify = NotifyMany::new();
+
+        let mut futs = Vec::new();
+        for _ in 0..8 {
+            let mut fut = tokio_test::task::spawn(notify.notified());
+            assert!(fut.poll().is_pending());
+            futs.push(fut);
+        }
+        assert_eq!(notify.waiters_len(), 8);
+
+        notify.notify_waiters();
+        assert_eq!(notify.waiters_len(), 0);
+
+        for mut fut in futs {
+            assert!(fut.poll().is_ready());
+        }
+    }
 }
\ No newline at end of file
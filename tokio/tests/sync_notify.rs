@@ -236,6 +236,38 @@ fn test_enable_consumes_permit() {
     future2.enter(|_, fut| assert!(!fut.enable()));
 }
 
+#[test]
+fn test_waiters_len_after_enable_without_poll() {
+    let notify = Notify::new();
+    let mut future = spawn(notify.notified());
+
+    // `enable()` registers the waiter just like a poll would.
+    future.enter(|_, fut| assert!(!fut.enable()));
+    assert_eq!(notify.waiters_len(), 1);
+
+    // Dropping without ever polling again must still unlink it.
+    drop(future);
+    assert_eq!(notify.waiters_len(), 0);
+}
+
+#[test]
+fn test_with_max_permits_banks_multiple() {
+    let notify = Notify::with_max_permits(2);
+
+    notify.notify_one();
+    notify.notify_one();
+    notify.notify_one(); // Beyond `max_permits`, this one is dropped.
+
+    let mut future1 = spawn(async { notify.notified().await });
+    assert_ready!(future1.poll());
+
+    let mut future2 = spawn(async { notify.notified().await });
+    assert_ready!(future2.poll());
+
+    let mut future3 = spawn(async { notify.notified().await });
+    assert_pending!(future3.poll());
+}
+
 #[test]
 fn test_waker_update() {
     use futures::task::noop_waker;